@@ -100,8 +100,41 @@ fn try_set_variant() {
     assert!(settings.is_maximized());
 }
 
+fn reset_and_default() {
+    #[gsettings_macro::gen_settings(
+        file = "./examples/test.gschema.xml",
+        id = "io.github.seadve.test"
+    )]
+    pub struct Settings;
+
+    let settings = Settings::new();
+
+    // An enum key resolves its schema default through the same conversion as
+    // the getter, so resetting it leaves the getter agreeing with the
+    // `default_` accessor.
+    settings.reset_preferred_audio_source();
+    assert_eq!(
+        settings.preferred_audio_source(),
+        settings.default_preferred_audio_source()
+    );
+
+    // Delay-apply buffers writes until applied, and can discard them.
+    settings.delay();
+    settings.set_window_width(640);
+    assert!(settings.has_unapplied());
+    settings.apply();
+    assert!(!settings.has_unapplied());
+    assert_eq!(settings.window_width(), 640);
+
+    settings.delay();
+    settings.set_window_width(1024);
+    settings.revert();
+    assert!(!settings.has_unapplied());
+}
+
 fn main() {
     no_id_defined();
     id_defined();
     try_set_variant();
+    reset_and_default();
 }
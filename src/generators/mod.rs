@@ -11,14 +11,22 @@ use syn::Ident;
 use crate::schema::{Enum as SchemaEnum, Key as SchemaKey, KeySignature as SchemaKeySignature};
 
 pub enum Override {
-    Define { arg_type: String, ret_type: String },
+    Define {
+        arg_type: String,
+        ret_type: String,
+    },
+    Map {
+        arg_type: String,
+        ret_type: String,
+        from_variant: String,
+        to_variant: String,
+    },
     Skip,
 }
 
 pub enum GetResult<'a> {
     Some(KeyGenerator<'a>),
     Skip,
-    Unknown,
 }
 
 pub struct KeyGenerators {
@@ -61,6 +69,17 @@ impl KeyGenerators {
                     self.signatures
                         .insert(signature, Context::new_dissimilar(&arg_type, &ret_type));
                 }
+                Override::Map {
+                    arg_type,
+                    ret_type,
+                    from_variant,
+                    to_variant,
+                } => {
+                    self.signatures.insert(
+                        signature,
+                        Context::new_mapped(&arg_type, &ret_type, &from_variant, &to_variant),
+                    );
+                }
                 Override::Skip => {
                     self.signature_skips.insert(signature);
                 }
@@ -76,6 +95,17 @@ impl KeyGenerators {
                     self.key_names
                         .insert(key_name, Context::new_dissimilar(&arg_type, &ret_type));
                 }
+                Override::Map {
+                    arg_type,
+                    ret_type,
+                    from_variant,
+                    to_variant,
+                } => {
+                    self.key_names.insert(
+                        key_name,
+                        Context::new_mapped(&arg_type, &ret_type, &from_variant, &to_variant),
+                    );
+                }
                 Override::Skip => {
                     self.key_name_skips.insert(key_name);
                 }
@@ -105,7 +135,16 @@ impl KeyGenerators {
         match key_signature {
             SchemaKeySignature::Type(type_) => match type_.as_str() {
                 "s" => GetResult::Some(string::key_generator(key)),
-                _ => GetResult::Unknown,
+                other => {
+                    let (arg_type, ret_type, consumed) = parse_signature(other);
+                    if consumed != other.len() {
+                        abort_call_site!("trailing characters in type signature `{}`", other);
+                    }
+                    GetResult::Some(KeyGenerator::new(
+                        key,
+                        Context::new_dissimilar(&arg_type, &ret_type),
+                    ))
+                }
             },
             SchemaKeySignature::Enum(ref enum_name) => GetResult::Some(enumeration::key_generator(
                 key,
@@ -122,6 +161,129 @@ impl KeyGenerators {
     }
 }
 
+/// Parses exactly one complete GVariant type from the front of `signature`,
+/// returning the mapped argument and return Rust types together with the
+/// number of characters consumed. The arg/ret distinction (e.g. `&str` vs
+/// `String`) is propagated through every container.
+fn parse_signature(signature: &str) -> (String, String, usize) {
+    let first = signature.chars().next().unwrap_or_else(|| {
+        abort_call_site!("unexpected end of type signature `{}`", signature)
+    });
+
+    match first {
+        'b' => basic("bool"),
+        'y' => basic("u8"),
+        'n' => basic("i16"),
+        'q' => basic("u16"),
+        'i' => basic("i32"),
+        'u' => basic("u32"),
+        'x' => basic("i64"),
+        't' => basic("u64"),
+        'd' => basic("f64"),
+        'h' => basic("i32"),
+        's' | 'o' | 'g' => ("&str".to_string(), "String".to_string(), 1),
+        'v' => basic("gio::glib::Variant"),
+        'm' => {
+            let (arg, ret, consumed) = parse_signature(&signature[1..]);
+            (
+                format!("Option<{}>", arg),
+                format!("Option<{}>", ret),
+                consumed + 1,
+            )
+        }
+        '(' => parse_tuple(signature),
+        'a' => parse_array(signature),
+        _ => abort_call_site!(
+            "unsupported type `{}` in type signature `{}`",
+            first,
+            signature
+        ),
+    }
+}
+
+fn basic(type_: &str) -> (String, String, usize) {
+    (type_.to_string(), type_.to_string(), 1)
+}
+
+/// Parses an array type. A `{KV}` dict entry is only legal directly after the
+/// leading `a`, in which case the array maps to a `HashMap<K, V>`; any other
+/// element type `T` maps to `&[T]` / `Vec<T>`.
+fn parse_array(signature: &str) -> (String, String, usize) {
+    let rest = &signature[1..];
+
+    if let Some(inner) = rest.strip_prefix('{') {
+        let (key_arg, key_ret, key_consumed) = parse_signature(inner);
+        let (value_arg, value_ret, value_consumed) = parse_signature(&inner[key_consumed..]);
+
+        if !inner[key_consumed + value_consumed..].starts_with('}') {
+            abort_call_site!(
+                "expected `}}` to close dict entry in type signature `{}`",
+                signature
+            );
+        }
+
+        // 'a' + '{' + key + value + '}'
+        let consumed = 1 + 1 + key_consumed + value_consumed + 1;
+        (
+            format!("std::collections::HashMap<{}, {}>", key_arg, value_arg),
+            format!("std::collections::HashMap<{}, {}>", key_ret, value_ret),
+            consumed,
+        )
+    } else {
+        let (arg, ret, consumed) = parse_signature(rest);
+        (format!("&[{}]", arg), format!("Vec<{}>", ret), consumed + 1)
+    }
+}
+
+/// Parses a tuple type `(T1…Tn)` into a Rust tuple.
+fn parse_tuple(signature: &str) -> (String, String, usize) {
+    let mut arg_types = Vec::new();
+    let mut ret_types = Vec::new();
+
+    // skip the leading `(`
+    let mut idx = 1;
+    loop {
+        let rest = &signature[idx..];
+
+        if rest.is_empty() {
+            abort_call_site!(
+                "expected `)` to close tuple in type signature `{}`",
+                signature
+            );
+        }
+
+        if rest.starts_with(')') {
+            idx += 1;
+            break;
+        }
+
+        let (arg, ret, consumed) = parse_signature(rest);
+        arg_types.push(arg);
+        ret_types.push(ret);
+        idx += consumed;
+    }
+
+    // glib only implements `FromVariant`/`ToVariant` for tuples up to 16 fields.
+    if arg_types.len() > 16 {
+        abort_call_site!(
+            "tuples with more than 16 fields are not supported by glib: `{}`",
+            signature
+        );
+    }
+
+    (rust_tuple(&arg_types), rust_tuple(&ret_types), idx)
+}
+
+/// Formats a list of field types as a Rust tuple, keeping the trailing comma
+/// that a single-element tuple requires (`(T,)` rather than `(T)`).
+fn rust_tuple(fields: &[String]) -> String {
+    if fields.len() == 1 {
+        format!("({},)", fields[0])
+    } else {
+        format!("({})", fields.join(", "))
+    }
+}
+
 pub struct KeyGenerator<'a> {
     key: &'a SchemaKey,
     context: Context,
@@ -136,6 +298,96 @@ impl<'a> KeyGenerator<'a> {
         Self { key, context }
     }
 
+    /// The field declaration for this key inside the generated
+    /// `SettingsSnapshot` struct. Keys that map to a type serde cannot handle
+    /// (e.g. a raw `gio::glib::Variant`) abort with a helpful message.
+    fn snapshot_field(&self) -> proc_macro2::TokenStream {
+        if !self.context.is_serializable() {
+            abort_call_site!(
+                "key `{}` maps to `{}`, which is not serde-serializable; remove \
+                 `serde = true` from `gen_settings`",
+                self.key.name,
+                self.context.ret_type
+            );
+        }
+
+        let field_ident = self.snapshot_field_ident();
+        let ret_type = self.ret_type();
+        quote! {
+            pub #field_ident: #ret_type,
+        }
+    }
+
+    /// The `SettingsSnapshot` initializer entry reading this key, used inside
+    /// the generated `snapshot` method.
+    fn snapshot_read(&self) -> proc_macro2::TokenStream {
+        let field_ident = self.snapshot_field_ident();
+        let getter_func_ident = self.getter_func_ident();
+        quote! {
+            #field_ident: self.#getter_func_ident(),
+        }
+    }
+
+    /// The statement writing this key back from a snapshot, used inside the
+    /// generated `load_snapshot` method.
+    fn snapshot_write(&self) -> proc_macro2::TokenStream {
+        let field_ident = self.snapshot_field_ident();
+        let key_name = self.key.name.as_str();
+        let set_expr = self.set_expr(quote! { snapshot.#field_ident });
+        quote! {
+            #set_expr
+                .unwrap_or_else(|err| panic!("failed to set value for key `{}`: {:?}", #key_name, err));
+        }
+    }
+
+    fn snapshot_field_ident(&self) -> Ident {
+        self.getter_func_ident()
+    }
+
+    /// The expression converting an already-read value into `ret_type`. This is
+    /// the single step both the getter and `default_<key>` apply on top of the
+    /// raw read, so a mapped override (`Override::Map`) runs its `from_variant`
+    /// transform for a default exactly as it does for a live read.
+    fn from_variant_expr(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match &self.context.from_variant {
+            Some(from_variant) => quote! { (#from_variant)(#value) },
+            None => value,
+        }
+    }
+
+    fn getter_func_ident(&self) -> Ident {
+        Ident::new(&self.key.name.to_snake_case(), Span::call_site())
+    }
+
+    fn ret_type(&self) -> syn::Type {
+        syn::parse_str::<syn::Type>(&self.context.ret_type)
+            .unwrap_or_else(|_| panic!("Invalid type `{}`", &self.context.ret_type))
+    }
+
+    /// Reads the stored value, running the from-variant transform when the
+    /// context is a mapped override.
+    fn get_expr(&self) -> proc_macro2::TokenStream {
+        let key_name = self.key.name.as_str();
+        self.from_variant_expr(quote! {
+            gio::prelude::SettingsExtManual::get(&self.0, #key_name)
+        })
+    }
+
+    /// Stores `value`, running the to-variant transform when the context is a
+    /// mapped override. The resulting expression evaluates to the fallible
+    /// `set` `Result`.
+    fn set_expr(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let key_name = self.key.name.as_str();
+        match &self.context.to_variant {
+            Some(to_variant) => quote! {
+                gio::prelude::SettingsExtManual::set(&self.0, #key_name, &(#to_variant)(#value))
+            },
+            None => quote! {
+                gio::prelude::SettingsExtManual::set(&self.0, #key_name, &#value)
+            },
+        }
+    }
+
     fn docs(&self) -> String {
         let mut buf = String::new();
         if let Some(ref summary) = self.key.summary {
@@ -178,7 +430,7 @@ impl quote::ToTokens for KeyGenerator<'_> {
         let docs = self.docs();
         let key_name = self.key.name.as_str();
         let key_name_snake_case = key_name.to_snake_case();
-        let getter_func_ident = Ident::new(&key_name_snake_case, Span::call_site());
+        let getter_func_ident = self.getter_func_ident();
 
         let connect_changed_func_ident = format_ident!("connect_{}_changed", getter_func_ident);
         let bind_func_ident = format_ident!("bind_{}", getter_func_ident);
@@ -205,12 +457,26 @@ impl quote::ToTokens for KeyGenerator<'_> {
 
         let setter_func_ident = format_ident!("set_{}", getter_func_ident);
         let try_setter_func_ident = format_ident!("try_set_{}", getter_func_ident);
+        let reset_func_ident = format_ident!("reset_{}", getter_func_ident);
+        let default_func_ident = format_ident!("default_{}", getter_func_ident);
 
-        let get_type = syn::parse_str::<syn::Type>(&self.context.ret_type)
-            .unwrap_or_else(|_| panic!("Invalid type `{}`", &self.context.ret_type));
+        let get_type = self.ret_type();
         let set_type = syn::parse_str::<syn::Type>(&self.context.arg_type)
             .unwrap_or_else(|_| panic!("Invalid type `{}`", &self.context.arg_type));
 
+        let get_expr = self.get_expr();
+        let set_expr = self.set_expr(quote! { value });
+
+        // The stored default is read and converted through the exact same
+        // path as the getter: `FromVariant` into the underlying type, then the
+        // `from_variant` transform of a mapped override on top. Keys relying on
+        // a plain `FromVariant` (including enums, whose generated type derives
+        // it) resolve their default identically to a live read.
+        let default_expr = self.from_variant_expr(quote! {
+            gio::glib::FromVariant::from_variant(&default_value)
+                .unwrap_or_else(|| panic!("failed to deserialize default value for key `{}`", #key_name))
+        });
+
         tokens.extend(quote! {
             #[doc = #docs]
             pub fn #setter_func_ident(&self, value: #set_type) {
@@ -219,22 +485,105 @@ impl quote::ToTokens for KeyGenerator<'_> {
 
             #[doc = #docs]
             pub fn #try_setter_func_ident(&self, value: #set_type) -> std::result::Result<(), gio::glib::BoolError> {
-                gio::prelude::SettingsExtManual::set(&self.0, #key_name, &value)
+                #set_expr
             }
 
             #[doc = #docs]
             pub fn #getter_func_ident(&self) -> #get_type {
-                gio::prelude::SettingsExtManual::get(&self.0, #key_name)
+                #get_expr
+            }
+
+            #[doc = #docs]
+            pub fn #reset_func_ident(&self) {
+                gio::prelude::SettingsExt::reset(&self.0, #key_name)
+            }
+
+            #[doc = #docs]
+            pub fn #default_func_ident(&self) -> #get_type {
+                let default_value = gio::prelude::SettingsExt::default_value(&self.0, #key_name)
+                    .unwrap_or_else(|| panic!("key `{}` does not have a default value", #key_name));
+                #default_expr
             }
         });
     }
 }
 
+/// Emits the delay-apply transactional API wrapping `gio::Settings`. Unlike the
+/// per-key accessors these are independent of the schema, so they are generated
+/// once per settings type. Callers use `delay` to start buffering writes,
+/// `apply`/`revert` to commit or discard them, and `has_unapplied` to check for
+/// pending changes.
+pub fn delay_apply_tokens() -> proc_macro2::TokenStream {
+    quote! {
+        /// Changes the settings object to delay-apply mode, buffering writes
+        /// until `apply` is called.
+        pub fn delay(&self) {
+            gio::prelude::SettingsExt::delay(&self.0)
+        }
+
+        /// Applies all changes buffered since the last `delay` call.
+        pub fn apply(&self) {
+            gio::prelude::SettingsExt::apply(&self.0)
+        }
+
+        /// Discards all changes buffered since the last `delay` call.
+        pub fn revert(&self) {
+            gio::prelude::SettingsExt::revert(&self.0)
+        }
+
+        /// Whether there are outstanding writes buffered in delay-apply mode.
+        pub fn has_unapplied(&self) -> bool {
+            gio::prelude::SettingsExt::has_unapplied(&self.0)
+        }
+    }
+}
+
+/// Emits the opt-in serde snapshot support for a generated settings type.
+///
+/// Activated by the `serde = true` flag on `gen_settings`; the emitted code
+/// needs the optional `serde` dependency to be enabled. The collected key fragments are
+/// assembled into a `SettingsSnapshot` struct whose fields mirror every key,
+/// giving callers a one-call export of the whole settings object to a
+/// serde-friendly value independent of the dconf backend. Pair it with
+/// [`snapshot_methods_tokens`] for the matching `snapshot`/`load_snapshot`
+/// methods.
+pub fn snapshot_struct_tokens(generators: &[KeyGenerator]) -> proc_macro2::TokenStream {
+    let fields = generators.iter().map(|generator| generator.snapshot_field());
+    quote! {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        pub struct SettingsSnapshot {
+            #(#fields)*
+        }
+    }
+}
+
+/// The `snapshot`/`load_snapshot` methods spliced into the generated settings
+/// `impl`. See [`snapshot_struct_tokens`].
+pub fn snapshot_methods_tokens(generators: &[KeyGenerator]) -> proc_macro2::TokenStream {
+    let reads = generators.iter().map(|generator| generator.snapshot_read());
+    let writes = generators.iter().map(|generator| generator.snapshot_write());
+    quote! {
+        /// Reads every key into a serde-serializable [`SettingsSnapshot`].
+        pub fn snapshot(&self) -> SettingsSnapshot {
+            SettingsSnapshot {
+                #(#reads)*
+            }
+        }
+
+        /// Writes every key back from a previously captured [`SettingsSnapshot`].
+        pub fn load_snapshot(&self, snapshot: &SettingsSnapshot) {
+            #(#writes)*
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Context {
     arg_type: String,
     ret_type: String,
     auxiliary: Option<proc_macro2::TokenStream>,
+    from_variant: Option<proc_macro2::TokenStream>,
+    to_variant: Option<proc_macro2::TokenStream>,
 }
 
 impl Context {
@@ -247,14 +596,52 @@ impl Context {
             arg_type: arg_type.to_string(),
             ret_type: ret_type.to_string(),
             auxiliary: None,
+            from_variant: None,
+            to_variant: None,
         }
     }
 
+    /// A context whose getter and setter are adapted by the given closures:
+    /// `from_variant` maps the stored value to `ret_type`, while `to_variant`
+    /// maps an `arg_type` value back before storing.
+    pub fn new_mapped(
+        arg_type: &str,
+        ret_type: &str,
+        from_variant: &str,
+        to_variant: &str,
+    ) -> Self {
+        let from_variant = syn::parse_str::<syn::Expr>(from_variant)
+            .unwrap_or_else(|_| panic!("Invalid from-variant expression `{}`", from_variant));
+        let to_variant = syn::parse_str::<syn::Expr>(to_variant)
+            .unwrap_or_else(|_| panic!("Invalid to-variant expression `{}`", to_variant));
+        Self {
+            arg_type: arg_type.to_string(),
+            ret_type: ret_type.to_string(),
+            auxiliary: None,
+            from_variant: Some(quote! { #from_variant }),
+            to_variant: Some(quote! { #to_variant }),
+        }
+    }
+
+    /// Whether `ret_type` can take part in a serde-derived snapshot. The only
+    /// non-serde type the generators ever emit is the raw glib `Variant`, so it
+    /// is matched as a whole path segment rather than a substring — a domain
+    /// type whose name merely contains `Variant` (e.g. a `Map` override
+    /// returning a `VariantColor`) is still serializable.
+    fn is_serializable(&self) -> bool {
+        !self
+            .ret_type
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|segment| segment == "Variant")
+    }
+
     pub fn new_with_aux(type_: &str, auxiliary: proc_macro2::TokenStream) -> Self {
         Self {
             arg_type: type_.to_string(),
             ret_type: type_.to_string(),
             auxiliary: Some(auxiliary),
+            from_variant: None,
+            to_variant: None,
         }
     }
 }
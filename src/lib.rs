@@ -0,0 +1,131 @@
+mod generators;
+mod schema;
+
+use std::{env, path::Path};
+
+use proc_macro::TokenStream;
+use proc_macro_error::{abort, abort_call_site, proc_macro_error};
+use quote::quote;
+use syn::{
+    parse_macro_input, punctuated::Punctuated, Expr, ExprLit, ItemStruct, Lit, MetaNameValue, Token,
+};
+
+use crate::{
+    generators::{GetResult, KeyGenerators},
+    schema::Schema,
+};
+
+/// Generates a typed wrapper around [`gio::Settings`] from a GSchema file.
+///
+/// Accepts `file` (the schema path, relative to the crate manifest), an
+/// optional `id`, and an optional `serde` flag. Passing `serde = true` emits a
+/// serde-serializable
+/// `SettingsSnapshot` companion together with `snapshot`/`load_snapshot`
+/// methods; it requires the optional `serde` dependency to be enabled.
+#[proc_macro_attribute]
+#[proc_macro_error]
+pub fn gen_settings(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr with Punctuated::<MetaNameValue, Token![,]>::parse_terminated);
+    let item = parse_macro_input!(item as ItemStruct);
+
+    let mut file = None;
+    let mut id = None;
+    let mut serde = false;
+
+    for arg in &args {
+        let name = arg.path.get_ident().map(ToString::to_string);
+        match name.as_deref() {
+            Some("file") => file = Some(lit_str(&arg.value)),
+            Some("id") => id = Some(lit_str(&arg.value)),
+            Some("serde") => serde = lit_bool(&arg.value),
+            _ => abort!(arg.path, "unknown `gen_settings` argument"),
+        }
+    }
+
+    let file = file.unwrap_or_else(|| abort_call_site!("expected a `file` argument"));
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let schema = Schema::from_path(&Path::new(&manifest_dir).join(&file));
+
+    let key_generators = KeyGenerators::with_defaults(schema.enums);
+
+    let mut generators = Vec::new();
+    let mut auxiliary = Vec::new();
+    for key in &schema.keys {
+        match key_generators.get(key) {
+            GetResult::Some(generator) => {
+                if let Some(aux) = generator.auxiliary() {
+                    auxiliary.push(aux);
+                }
+                generators.push(generator);
+            }
+            GetResult::Skip => {}
+        }
+    }
+
+    let delay_apply = generators::delay_apply_tokens();
+
+    let (snapshot_struct, snapshot_methods) = if serde {
+        (
+            generators::snapshot_struct_tokens(&generators),
+            generators::snapshot_methods_tokens(&generators),
+        )
+    } else {
+        (quote!(), quote!())
+    };
+
+    let constructor = match id {
+        Some(id) => quote! {
+            pub fn new() -> Self {
+                Self(gio::Settings::new(#id))
+            }
+        },
+        None => quote! {
+            pub fn new(schema_id: &str) -> Self {
+                Self(gio::Settings::new(schema_id))
+            }
+        },
+    };
+
+    let attrs = &item.attrs;
+    let vis = &item.vis;
+    let ident = &item.ident;
+
+    quote! {
+        #(#auxiliary)*
+
+        #(#attrs)*
+        #vis struct #ident(gio::Settings);
+
+        impl #ident {
+            #constructor
+
+            #(#generators)*
+
+            #delay_apply
+
+            #snapshot_methods
+        }
+
+        #snapshot_struct
+    }
+    .into()
+}
+
+fn lit_str(value: &Expr) -> String {
+    match value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => lit.value(),
+        _ => abort!(value, "expected a string literal"),
+    }
+}
+
+fn lit_bool(value: &Expr) -> bool {
+    match value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Bool(lit),
+            ..
+        }) => lit.value(),
+        _ => abort!(value, "expected a boolean literal"),
+    }
+}